@@ -3,40 +3,50 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-const JWT_SECRET: &[u8] = b"i'm_tired_WB";
+use crate::config::JwtConfig;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: Uuid,
     pub exp: u64,
+    /// Unix timestamp (microseconds) of the user's `session_epoch` at
+    /// issuance, used to detect tokens invalidated by a later logout.
+    /// Microsecond precision matters: Postgres's `TIMESTAMPTZ` carries
+    /// microseconds, and truncating to whole seconds lets a same-second
+    /// logout silently fail to revoke the token that triggered it.
+    pub epoch: i64,
 }
 
-pub fn create_jwt(user_id: Uuid) -> String {
+pub fn create_jwt(user_id: Uuid, session_epoch: chrono::DateTime<chrono::Utc>, config: &JwtConfig) -> String {
     let expiration = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
-        .as_secs() + 24 * 3600;
+        .as_secs() + config.expires_in.as_secs();
 
     let claims = Claims {
         sub: user_id,
         exp: expiration,
+        epoch: session_epoch.timestamp_micros(),
     };
 
     encode(
         &Header::default(),
         &claims,
-        &EncodingKey::from_secret(JWT_SECRET),
+        &EncodingKey::from_secret(&config.secret),
     )
         .unwrap()
 }
 
-pub fn verify_jwt(token: &str) -> Option<Uuid> {
+/// Decodes and validates a JWT's signature and expiry. This does not consult
+/// the database — callers must separately check the returned `Claims::epoch`
+/// against the user's current `session_epoch` to reject revoked tokens.
+pub fn verify_jwt(token: &str, config: &JwtConfig) -> Option<Claims> {
     let validation = Validation::default();
     decode::<Claims>(
         token,
-        &DecodingKey::from_secret(JWT_SECRET),
+        &DecodingKey::from_secret(&config.secret),
         &validation,
     )
         .ok()
-        .map(|data| data.claims.sub)
-}
\ No newline at end of file
+        .map(|data| data.claims)
+}
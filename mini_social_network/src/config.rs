@@ -0,0 +1,42 @@
+use std::env;
+use std::time::Duration;
+
+/// Application configuration, loaded once from environment variables at startup.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub database_url: String,
+    pub bind_addr: String,
+    pub jwt: JwtConfig,
+}
+
+/// JWT signing secret and expiration, carried in the axum state so handlers
+/// never reach for a hardcoded secret.
+#[derive(Debug, Clone)]
+pub struct JwtConfig {
+    pub secret: Vec<u8>,
+    pub expires_in: Duration,
+}
+
+impl Config {
+    /// Loads configuration from the environment, falling back to sensible
+    /// defaults for local development.
+    pub fn from_env() -> Self {
+        Self {
+            database_url: env::var("DATABASE_URL")
+                .unwrap_or_else(|_| "postgres://wb:wb@localhost/wb_db".to_string()),
+            bind_addr: env::var("BIND_ADDR").unwrap_or_else(|_| "127.0.0.1:3000".to_string()),
+            jwt: JwtConfig {
+                secret: env::var("JWT_SECRET")
+                    .unwrap_or_else(|_| "i'm_tired_WB".to_string())
+                    .into_bytes(),
+                expires_in: Duration::from_secs(
+                    env::var("JWT_EXPIRES_IN")
+                        .or_else(|_| env::var("JWT_MAXAGE"))
+                        .ok()
+                        .and_then(|v| v.parse::<u64>().ok())
+                        .unwrap_or(24 * 3600),
+                ),
+            },
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
+use chrono::{DateTime, Utc};
 use tokio_postgres::{Client, Error};
-use crate::models::{User, Post};
+use crate::models::{User, Post, FeedPost};
 use uuid::Uuid;
 
 #[derive(Clone)]
@@ -22,7 +23,12 @@ impl Database {
                 CREATE TABLE IF NOT EXISTS users (
                     id UUID PRIMARY KEY,
                     username TEXT UNIQUE NOT NULL,
-                    password_hash TEXT NOT NULL
+                    password_hash TEXT NOT NULL,
+                    name TEXT,
+                    email TEXT UNIQUE,
+                    bio TEXT,
+                    avatar TEXT,
+                    session_epoch TIMESTAMPTZ NOT NULL DEFAULT NOW()
                 );
 
                 CREATE TABLE IF NOT EXISTS posts (
@@ -46,8 +52,18 @@ impl Database {
     pub async fn create_user(&self, user: &User) -> Result<bool, Error> {
         let result = self.client
             .execute(
-                "INSERT INTO users (id, username, password_hash) VALUES ($1, $2, $3)",
-                &[&user.id, &user.username, &user.password_hash],
+                "INSERT INTO users (id, username, password_hash, name, email, bio, avatar, session_epoch)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+                &[
+                    &user.id,
+                    &user.username,
+                    &user.password_hash,
+                    &user.name,
+                    &user.email,
+                    &user.bio,
+                    &user.avatar,
+                    &user.session_epoch,
+                ],
             )
             .await?;
         Ok(result > 0)
@@ -56,16 +72,60 @@ impl Database {
     pub async fn get_user_by_username(&self, username: &str) -> Result<Option<User>, Error> {
         let row = self.client
             .query_opt(
-                "SELECT id, username, password_hash FROM users WHERE username = $1",
+                "SELECT id, username, password_hash, name, email, bio, avatar, session_epoch
+                 FROM users WHERE username = $1",
                 &[&username],
             )
             .await?;
 
-        Ok(row.map(|row| User {
+        Ok(row.map(Self::row_to_user))
+    }
+
+    pub async fn get_user_by_id(&self, id: Uuid) -> Result<Option<User>, Error> {
+        let row = self.client
+            .query_opt(
+                "SELECT id, username, password_hash, name, email, bio, avatar, session_epoch
+                 FROM users WHERE id = $1",
+                &[&id],
+            )
+            .await?;
+
+        Ok(row.map(Self::row_to_user))
+    }
+
+    pub async fn update_avatar(&self, user_id: Uuid, avatar: &str) -> Result<bool, Error> {
+        let result = self.client
+            .execute(
+                "UPDATE users SET avatar = $1 WHERE id = $2",
+                &[&avatar, &user_id],
+            )
+            .await?;
+        Ok(result > 0)
+    }
+
+    /// Bumps the user's session epoch to now, instantly invalidating every
+    /// JWT issued before this call.
+    pub async fn bump_session_epoch(&self, user_id: Uuid) -> Result<bool, Error> {
+        let result = self.client
+            .execute(
+                "UPDATE users SET session_epoch = NOW() WHERE id = $1",
+                &[&user_id],
+            )
+            .await?;
+        Ok(result > 0)
+    }
+
+    fn row_to_user(row: tokio_postgres::Row) -> User {
+        User {
             id: row.get(0),
             username: row.get(1),
             password_hash: row.get(2),
-        }))
+            name: row.get(3),
+            email: row.get(4),
+            bio: row.get(5),
+            avatar: row.get(6),
+            session_epoch: row.get(7),
+        }
     }
 
     pub async fn create_post(&self, post: &Post) -> Result<bool, Error> {
@@ -95,48 +155,121 @@ impl Database {
         }))
     }
 
-    pub async fn delete_post(&self, id: Uuid, user_id: Uuid) -> Result<bool, Error> {
-        if !self.check_post_ownership(id, user_id).await? {
-            return Ok(false);
+    /// Returns the global feed ordered by `created_at DESC`, keyset-paginated
+    /// on `before`. When `viewer_id` is set, each post carries whether that
+    /// user has liked it.
+    pub async fn get_feed(
+        &self,
+        before: Option<DateTime<Utc>>,
+        limit: i64,
+        viewer_id: Option<Uuid>,
+    ) -> Result<Vec<FeedPost>, Error> {
+        let rows = self.client
+            .query(
+                "SELECT p.id, p.user_id, u.username, p.content, p.likes_count, p.created_at,
+                        CASE WHEN $3::uuid IS NULL THEN NULL
+                             ELSE EXISTS(SELECT 1 FROM likes l WHERE l.post_id = p.id AND l.user_id = $3)
+                        END AS liked_by_me
+                 FROM posts p
+                 JOIN users u ON u.id = p.user_id
+                 WHERE $1::timestamptz IS NULL OR p.created_at < $1
+                 ORDER BY p.created_at DESC
+                 LIMIT $2",
+                &[&before, &limit, &viewer_id],
+            )
+            .await?;
+
+        Ok(rows.into_iter().map(Self::row_to_feed_post).collect())
+    }
+
+    /// Same as [`Database::get_feed`], scoped to a single author.
+    pub async fn get_user_posts(
+        &self,
+        author_id: Uuid,
+        before: Option<DateTime<Utc>>,
+        limit: i64,
+        viewer_id: Option<Uuid>,
+    ) -> Result<Vec<FeedPost>, Error> {
+        let rows = self.client
+            .query(
+                "SELECT p.id, p.user_id, u.username, p.content, p.likes_count, p.created_at,
+                        CASE WHEN $3::uuid IS NULL THEN NULL
+                             ELSE EXISTS(SELECT 1 FROM likes l WHERE l.post_id = p.id AND l.user_id = $3)
+                        END AS liked_by_me
+                 FROM posts p
+                 JOIN users u ON u.id = p.user_id
+                 WHERE p.user_id = $4
+                   AND ($1::timestamptz IS NULL OR p.created_at < $1)
+                 ORDER BY p.created_at DESC
+                 LIMIT $2",
+                &[&before, &limit, &viewer_id, &author_id],
+            )
+            .await?;
+
+        Ok(rows.into_iter().map(Self::row_to_feed_post).collect())
+    }
+
+    fn row_to_feed_post(row: tokio_postgres::Row) -> FeedPost {
+        FeedPost {
+            id: row.get(0),
+            user_id: row.get(1),
+            username: row.get(2),
+            content: row.get(3),
+            likes_count: row.get(4),
+            created_at: row.get(5),
+            liked_by_me: row.get(6),
         }
+    }
 
+    /// Deletes the post iff it belongs to `user_id`, relying on the
+    /// `ON DELETE CASCADE` on `likes` to clean up its likes. Ownership check
+    /// and deletion happen as a single atomic statement.
+    pub async fn delete_post(&self, id: Uuid, user_id: Uuid) -> Result<bool, Error> {
         let result = self.client.execute(
-            "DELETE FROM likes WHERE post_id = $1",
-            &[&id],
+            "DELETE FROM posts WHERE id = $1 AND user_id = $2",
+            &[&id, &user_id],
         ).await?;
 
         Ok(result > 0)
     }
 
+    /// Records a like and increments `likes_count` in a single statement, so
+    /// the two never drift apart. Returns `false` if the user already liked
+    /// the post. For a post that does not exist the insert violates the
+    /// `likes.post_id` foreign key and this returns `Err`, so callers confirm
+    /// the post exists first (e.g. via [`Database::check_post_exists`]).
     pub async fn like_post(&self, post_id: Uuid, user_id: Uuid) -> Result<bool, Error> {
-        if !self.check_post_exists(post_id).await? {
-            return Ok(false);
-        }
-
-        if self.like_exists(post_id, user_id).await? {
-            return Ok(false);
-        }
-
-        self.client.execute(
-            "INSERT INTO likes (post_id, user_id) VALUES ($1, $2)",
+        let result = self.client.execute(
+            "WITH ins AS (
+                INSERT INTO likes (post_id, user_id) VALUES ($1, $2)
+                ON CONFLICT DO NOTHING
+                RETURNING post_id
+             )
+             UPDATE posts SET likes_count = likes_count + 1
+             WHERE id = $1 AND EXISTS (SELECT 1 FROM ins)",
             &[&post_id, &user_id],
         ).await?;
 
-        Ok(true)
+        Ok(result > 0)
     }
 
-    async fn check_post_ownership(&self, post_id: Uuid, user_id: Uuid) -> Result<bool, Error> {
-        let result = self.client
-            .query_opt(
-                "SELECT EXISTS(SELECT 1 FROM posts WHERE id = $1 AND user_id = $2)",
-                &[&post_id, &user_id],
-            )
-            .await?;
+    /// Inverse of [`Database::like_post`]: removes the like and decrements
+    /// `likes_count` atomically. Returns `false` if the like didn't exist.
+    pub async fn unlike_post(&self, post_id: Uuid, user_id: Uuid) -> Result<bool, Error> {
+        let result = self.client.execute(
+            "WITH del AS (
+                DELETE FROM likes WHERE post_id = $1 AND user_id = $2
+                RETURNING post_id
+             )
+             UPDATE posts SET likes_count = GREATEST(likes_count - 1, 0)
+             WHERE id = $1 AND EXISTS (SELECT 1 FROM del)",
+            &[&post_id, &user_id],
+        ).await?;
 
-        Ok(result.map(|row| row.get::<_, bool>(0)).unwrap_or(false))
+        Ok(result > 0)
     }
 
-    async fn check_post_exists(&self, post_id: Uuid) -> Result<bool, Error> {
+    pub async fn check_post_exists(&self, post_id: Uuid) -> Result<bool, Error> {
         let result = self.client
             .query_opt(
                 "SELECT EXISTS(SELECT 1 FROM posts WHERE id = $1)",
@@ -146,15 +279,4 @@ impl Database {
 
         Ok(result.map(|row| row.get::<_, bool>(0)).unwrap_or(false))
     }
-
-    async fn like_exists(&self, post_id: Uuid, user_id: Uuid) -> Result<bool, Error> {
-        let result = self.client
-            .query_opt(
-                "SELECT EXISTS(SELECT 1 FROM likes WHERE post_id = $1 AND user_id = $2)",
-                &[&post_id, &user_id],
-            )
-            .await?;
-
-        Ok(result.map(|row| row.get::<_, bool>(0)).unwrap_or(false))
-    }
 }
\ No newline at end of file
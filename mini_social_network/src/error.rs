@@ -0,0 +1,96 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use thiserror::Error;
+use tokio_postgres::error::SqlState;
+
+/// Single error type for all handlers, rendered as `{"status", "message"}` JSON.
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("database error: {0}")]
+    Database(tokio_postgres::Error),
+
+    #[error("missing credentials")]
+    MissingCredentials,
+
+    #[error("invalid credentials")]
+    InvalidCredentials,
+
+    #[error("missing token")]
+    MissingToken,
+
+    #[error("invalid token")]
+    InvalidToken,
+
+    #[error("{0} not found")]
+    NotFound(String),
+
+    #[error("user already exists")]
+    UserExists,
+
+    #[error("post already liked")]
+    AlreadyLiked,
+
+    #[error("{0}")]
+    BadRequest(String),
+
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            AppError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::MissingCredentials => StatusCode::BAD_REQUEST,
+            AppError::InvalidCredentials => StatusCode::UNAUTHORIZED,
+            AppError::MissingToken => StatusCode::UNAUTHORIZED,
+            AppError::InvalidToken => StatusCode::UNAUTHORIZED,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::UserExists => StatusCode::CONFLICT,
+            AppError::AlreadyLiked => StatusCode::CONFLICT,
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        // Database/Internal messages can carry SQL state, table/constraint
+        // names, or raw OS/driver error text — log the real error server-side
+        // and never echo it to the client.
+        let message = match &self {
+            AppError::Database(_) | AppError::Internal(_) => {
+                eprintln!("internal error: {}", self);
+                "internal server error".to_string()
+            }
+            _ => self.to_string(),
+        };
+
+        let body = Json(json!({
+            "status": status.as_u16(),
+            "message": message,
+        }));
+
+        (status, body).into_response()
+    }
+}
+
+/// Maps a unique-constraint violation on `users` to `UserExists` instead of a
+/// generic 500, so duplicate-username registration gives a clear error.
+impl From<tokio_postgres::Error> for AppError {
+    fn from(err: tokio_postgres::Error) -> Self {
+        let is_users_unique_violation = err
+            .as_db_error()
+            .map(|db_err| {
+                db_err.code() == &SqlState::UNIQUE_VIOLATION && db_err.table() == Some("users")
+            })
+            .unwrap_or(false);
+
+        if is_users_unique_violation {
+            AppError::UserExists
+        } else {
+            AppError::Database(err)
+        }
+    }
+}
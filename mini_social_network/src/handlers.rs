@@ -1,100 +1,119 @@
 use axum::{
-    extract::{State, Path, Json},
+    extract::{State, Path, Json, Multipart, Query},
     http::{HeaderMap, StatusCode, header::AUTHORIZATION},
     response::IntoResponse,
 };
 use bcrypt::{hash, verify, DEFAULT_COST};
+use image::{imageops::FilterType, ImageFormat};
 use uuid::Uuid;
 use crate::{
+    AppState,
+    config::JwtConfig,
     db::Database,
-    models::{User, Post, RegisterRequest, LoginRequest, CreatePostRequest},
+    error::AppError,
+    models::{User, Post, UserProfile, FeedQuery, RegisterRequest, LoginRequest, CreatePostRequest},
     auth::{create_jwt, verify_jwt},
 };
 use serde_json::json;
 
+/// Directory avatar images are written to, relative to the process cwd.
+const AVATAR_DIR: &str = "avatars";
+/// Reject uploads larger than this before we even try to decode them.
+const MAX_AVATAR_BYTES: usize = 5 * 1024 * 1024;
+/// Re-encoded avatars are capped so their longest side never exceeds this.
+const AVATAR_MAX_SIDE: u32 = 256;
+/// Default page size for feed endpoints when `limit` is omitted.
+const DEFAULT_FEED_LIMIT: i64 = 20;
+/// Feed endpoints never return more than this many posts per page.
+const MAX_FEED_LIMIT: i64 = 50;
+
 pub async fn register(
-    State(db): State<Database>,
+    State(state): State<AppState>,
     Json(req): Json<RegisterRequest>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+) -> Result<impl IntoResponse, AppError> {
+    if req.username.is_empty() || req.password.is_empty() {
+        return Err(AppError::MissingCredentials);
+    }
+
     let password_hash = hash(req.password.as_bytes(), DEFAULT_COST)
-        .map_err(|_| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Failed to hash password"}))
-        ))?;
+        .map_err(|e| AppError::Internal(format!("failed to hash password: {}", e)))?;
 
     let user = User {
         id: Uuid::new_v4(),
         username: req.username,
         password_hash,
+        name: None,
+        email: None,
+        bio: None,
+        avatar: None,
+        session_epoch: chrono::Utc::now(),
     };
 
-    let result = db.create_user(&user).await
-        .map_err(|_| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Database error"}))
-        ))?;
+    state.db.create_user(&user).await?;
 
-    if !result {
-        return Err((StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Database error"}))
-        ))
-    }
-
-    let token = create_jwt(user.id);
+    let token = create_jwt(user.id, user.session_epoch, &state.jwt);
     Ok((StatusCode::CREATED, Json(json!({ "token": token }))))
 }
 
 pub async fn login(
-    State(db): State<Database>,
+    State(state): State<AppState>,
     Json(req): Json<LoginRequest>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let user = db.get_user_by_username(&req.username).await
-        .map_err(|_| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Database error"}))
-        ))?
-        .ok_or_else(|| (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({"error": "Invalid credentials"}))
-        ))?;
-
-    if !verify(req.password.as_bytes(), &user.password_hash)
-        .map_err(|_| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Failed to verify password"}))
-        ))? {
-        return Err((
-            StatusCode::UNAUTHORIZED,
-            Json(json!({"error": "Invalid credentials"}))
-        ));
+) -> Result<impl IntoResponse, AppError> {
+    if req.username.is_empty() || req.password.is_empty() {
+        return Err(AppError::MissingCredentials);
+    }
+
+    let user = state.db.get_user_by_username(&req.username).await?
+        .ok_or(AppError::InvalidCredentials)?;
+
+    if !verify(req.password.as_bytes(), &user.password_hash).unwrap_or(false) {
+        return Err(AppError::InvalidCredentials);
     }
 
-    let token = create_jwt(user.id);
+    let token = create_jwt(user.id, user.session_epoch, &state.jwt);
     Ok(Json(json!({ "token": token })))
 }
 
-fn extract_user_id(headers: &HeaderMap) -> Result<Uuid, (StatusCode, Json<serde_json::Value>)> {
+/// Decodes the bearer token and confirms it hasn't been revoked by a later
+/// logout: the embedded `epoch` must not be older than the user's current
+/// `session_epoch`.
+async fn extract_user_id(headers: &HeaderMap, jwt: &JwtConfig, db: &Database) -> Result<Uuid, AppError> {
     let token = headers
         .get(AUTHORIZATION)
         .and_then(|value| value.to_str().ok())
         .and_then(|value| value.strip_prefix("Bearer "))
-        .ok_or_else(|| (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({"error": "Missing authorization header"}))
-        ))?;
-
-    verify_jwt(token).ok_or_else(|| (
-        StatusCode::UNAUTHORIZED,
-        Json(json!({"error": "Invalid token"}))
-    ))
+        .ok_or(AppError::MissingToken)?;
+
+    let claims = verify_jwt(token, jwt).ok_or(AppError::InvalidToken)?;
+
+    let user = db.get_user_by_id(claims.sub).await?
+        .ok_or(AppError::InvalidToken)?;
+
+    if claims.epoch < user.session_epoch.timestamp_micros() {
+        return Err(AppError::InvalidToken);
+    }
+
+    Ok(claims.sub)
+}
+
+/// Like [`extract_user_id`], but treats a missing, expired, or malformed
+/// token as an anonymous request instead of an error — used by endpoints
+/// that are readable by anyone but personalized for authenticated viewers.
+/// Only a genuine database failure is propagated.
+async fn try_extract_user_id(headers: &HeaderMap, jwt: &JwtConfig, db: &Database) -> Result<Option<Uuid>, AppError> {
+    match extract_user_id(headers, jwt, db).await {
+        Ok(user_id) => Ok(Some(user_id)),
+        Err(AppError::MissingToken) | Err(AppError::InvalidToken) => Ok(None),
+        Err(err) => Err(err),
+    }
 }
 
 pub async fn create_post(
-    State(db): State<Database>,
+    State(state): State<AppState>,
     headers: HeaderMap,
     Json(req): Json<CreatePostRequest>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let user_id = extract_user_id(&headers)?;
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = extract_user_id(&headers, &state.jwt, &state.db).await?;
 
     let post = Post {
         id: Uuid::new_v4(),
@@ -104,80 +123,169 @@ pub async fn create_post(
         created_at: chrono::Utc::now(),
     };
 
-    let result = db.create_post(&post).await
-        .map_err(|_| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Database error"}))
-        ))?;
-
-    if !result {
-        return Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Database error"}))
-        ));
-    }
+    state.db.create_post(&post).await?;
 
     Ok((StatusCode::CREATED, Json(json!(post))))
 }
 
 pub async fn get_post(
-    State(db): State<Database>,
+    State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let post = db.get_post(id).await
-        .map_err(|_| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Database error"}))
-        ))?
-        .ok_or_else(|| (
-            StatusCode::NOT_FOUND,
-            Json(json!({"error": "Post not found"}))
-        ))?;
+) -> Result<impl IntoResponse, AppError> {
+    let post = state.db.get_post(id).await?
+        .ok_or_else(|| AppError::NotFound("post".to_string()))?;
 
     Ok(Json(post))
 }
 
+pub async fn get_feed(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<FeedQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let viewer_id = try_extract_user_id(&headers, &state.jwt, &state.db).await?;
+    let limit = query.limit.unwrap_or(DEFAULT_FEED_LIMIT).clamp(1, MAX_FEED_LIMIT);
+
+    let posts = state.db.get_feed(query.before, limit, viewer_id).await?;
+    let next_cursor = posts.last().map(|p| p.created_at);
+
+    Ok(Json(json!({ "posts": posts, "next_cursor": next_cursor })))
+}
+
+pub async fn get_user_feed(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(author_id): Path<Uuid>,
+    Query(query): Query<FeedQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let viewer_id = try_extract_user_id(&headers, &state.jwt, &state.db).await?;
+    let limit = query.limit.unwrap_or(DEFAULT_FEED_LIMIT).clamp(1, MAX_FEED_LIMIT);
+
+    let posts = state.db.get_user_posts(author_id, query.before, limit, viewer_id).await?;
+    let next_cursor = posts.last().map(|p| p.created_at);
+
+    Ok(Json(json!({ "posts": posts, "next_cursor": next_cursor })))
+}
+
 pub async fn delete_post(
-    State(db): State<Database>,
+    State(state): State<AppState>,
     headers: HeaderMap,
     Path(id): Path<Uuid>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let user_id = extract_user_id(&headers)?;
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = extract_user_id(&headers, &state.jwt, &state.db).await?;
 
-    let result = db.delete_post(id, user_id).await
-        .map_err(|_| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Database error"}))
-        ))?;
+    let result = state.db.delete_post(id, user_id).await?;
 
     if !result {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(json!({"error": "Post not found or unauthorized"}))
-        ));
+        return Err(AppError::NotFound("post".to_string()));
     }
 
     Ok(StatusCode::NO_CONTENT)
 }
 
 pub async fn like_post(
-    State(db): State<Database>,
+    State(state): State<AppState>,
     headers: HeaderMap,
     Path(id): Path<Uuid>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let user_id = extract_user_id(&headers)?;
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = extract_user_id(&headers, &state.jwt, &state.db).await?;
 
-    let result = db.like_post(id, user_id).await
-        .map_err(|_| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Database error"}))
-        ))?;
+    if !state.db.check_post_exists(id).await? {
+        return Err(AppError::NotFound("post".to_string()));
+    }
 
-    if !result {
-            return Err((
-            StatusCode::NOT_ACCEPTABLE,
-            Json(json!({"error": "Post not found or already liked"}))
-        ));
+    if !state.db.like_post(id, user_id).await? {
+        return Err(AppError::AlreadyLiked);
     }
     Ok(StatusCode::OK)
-}
\ No newline at end of file
+}
+
+pub async fn unlike_post(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = extract_user_id(&headers, &state.jwt, &state.db).await?;
+
+    let result = state.db.unlike_post(id, user_id).await?;
+
+    if !result {
+        return Err(AppError::NotFound("like".to_string()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_user_profile(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = state.db.get_user_by_id(id).await?
+        .ok_or_else(|| AppError::NotFound("user".to_string()))?;
+
+    Ok(Json(UserProfile::from(user)))
+}
+
+pub async fn upload_avatar(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = extract_user_id(&headers, &state.jwt, &state.db).await?;
+
+    let field = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("invalid multipart upload: {}", e)))?
+        .ok_or_else(|| AppError::BadRequest("missing avatar file".to_string()))?;
+
+    let content_type = field.content_type().unwrap_or("").to_string();
+    if !content_type.starts_with("image/") {
+        return Err(AppError::BadRequest("avatar must be an image".to_string()));
+    }
+
+    let bytes = field
+        .bytes()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("failed to read avatar upload: {}", e)))?;
+
+    if bytes.len() > MAX_AVATAR_BYTES {
+        return Err(AppError::BadRequest("avatar exceeds maximum upload size".to_string()));
+    }
+
+    let image = image::load_from_memory(&bytes)
+        .map_err(|_| AppError::BadRequest("could not decode avatar image".to_string()))?
+        .resize(AVATAR_MAX_SIDE, AVATAR_MAX_SIDE, FilterType::Lanczos3);
+
+    std::fs::create_dir_all(AVATAR_DIR)
+        .map_err(|e| AppError::Internal(format!("failed to prepare avatar storage: {}", e)))?;
+
+    let path = format!("{}/{}.png", AVATAR_DIR, user_id);
+    image
+        .save_with_format(&path, ImageFormat::Png)
+        .map_err(|e| AppError::Internal(format!("failed to save avatar: {}", e)))?;
+
+    state.db.update_avatar(user_id, &path).await?;
+
+    Ok(Json(json!({ "avatar": path })))
+}
+
+/// Invalidates every JWT issued to the caller so far. Because tokens are
+/// stateless, there is no per-device session to target — this and
+/// `logout_all` both bump `session_epoch`, revoking all of them at once.
+pub async fn logout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = extract_user_id(&headers, &state.jwt, &state.db).await?;
+    state.db.bump_session_epoch(user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn logout_all(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = extract_user_id(&headers, &state.jwt, &state.db).await?;
+    state.db.bump_session_epoch(user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,5 +1,7 @@
 mod auth;
+mod config;
 mod db;
+mod error;
 mod handlers;
 mod models;
 
@@ -15,15 +17,31 @@ use crate::handlers::{
     login,
     create_post,
     get_post,
+    get_feed,
+    get_user_feed,
     delete_post,
-    like_post
+    like_post,
+    unlike_post,
+    get_user_profile,
+    upload_avatar,
+    logout,
+    logout_all,
 };
+use crate::config::Config;
 use crate::db::Database;
 
+#[derive(Clone)]
+pub struct AppState {
+    pub db: Database,
+    pub jwt: config::JwtConfig,
+}
+
 #[tokio::main]
 async fn main() {
+    let config = Config::from_env();
+
     let (client, connection) = tokio_postgres::connect(
-        "postgres://wb:wb@localhost/wb_db",
+        &config.database_url,
         NoTls,
     ).await.unwrap();
 
@@ -36,17 +54,28 @@ async fn main() {
     let db = Database::new(client);
     db.init().await.unwrap();
 
+    let state = AppState {
+        db,
+        jwt: config.jwt.clone(),
+    };
+
     let app = Router::new()
         .route("/register", post(register))
         .route("/login", post(login))
         .route("/posts", post(create_post))
+        .route("/posts", get(get_feed))
         .route("/posts/:id", get(get_post))
         .route("/posts/:id", delete(delete_post))
         .route("/posts/:id/likes", post(like_post))
-        .with_state(db.clone());
+        .route("/posts/:id/likes", delete(unlike_post))
+        .route("/users/:id", get(get_user_profile))
+        .route("/users/:id/posts", get(get_user_feed))
+        .route("/users/me/avatar", post(upload_avatar))
+        .route("/logout", post(logout))
+        .route("/logout/all", post(logout_all))
+        .with_state(state);
 
-    let addr = "127.0.0.1:3000";
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    println!("Mini social network server started at http://{}", addr);
+    let listener = tokio::net::TcpListener::bind(&config.bind_addr).await.unwrap();
+    println!("Mini social network server started at http://{}", config.bind_addr);
     axum::serve(listener, app).await.unwrap();
-}
\ No newline at end of file
+}
@@ -6,6 +6,34 @@ pub struct User {
     pub id: Uuid,
     pub username: String,
     pub password_hash: String,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub bio: Option<String>,
+    pub avatar: Option<String>,
+    pub session_epoch: chrono::DateTime<chrono::Utc>,
+}
+
+/// Public view of a `User`, returned from profile endpoints — never carries
+/// `password_hash` or `email`, which are private to the account owner.
+#[derive(Debug, Serialize)]
+pub struct UserProfile {
+    pub id: Uuid,
+    pub username: String,
+    pub name: Option<String>,
+    pub bio: Option<String>,
+    pub avatar: Option<String>,
+}
+
+impl From<User> for UserProfile {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            username: user.username,
+            name: user.name,
+            bio: user.bio,
+            avatar: user.avatar,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -32,4 +60,24 @@ pub struct LoginRequest {
 #[derive(Debug, Deserialize)]
 pub struct CreatePostRequest {
     pub content: String,
-}
\ No newline at end of file
+}
+
+/// A feed entry: a `Post` joined with its author's username and, for
+/// authenticated requests, whether the viewer has liked it.
+#[derive(Debug, Serialize)]
+pub struct FeedPost {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub username: String,
+    pub content: String,
+    pub likes_count: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub liked_by_me: Option<bool>,
+}
+
+/// Keyset pagination query for feed endpoints: `?before=<created_at>&limit=<n>`.
+#[derive(Debug, Deserialize)]
+pub struct FeedQuery {
+    pub before: Option<chrono::DateTime<chrono::Utc>>,
+    pub limit: Option<i64>,
+}